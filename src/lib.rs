@@ -1,9 +1,26 @@
 use rand::Rng;
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
 
 mod default_adjectives;
 mod default_nouns;
 
+/// The unit a token alphabet is indexed in when it's derived from
+/// `token_chars` (i.e. when `token_pattern` is empty).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenUnit {
+    /// Index by Unicode scalar value (`char`). This is the default, and
+    /// matches the historical behavior of `token_chars`.
+    #[default]
+    Scalar,
+    /// Index by Unicode grapheme cluster, so multi-codepoint
+    /// user-perceived characters (an emoji with a modifier, a ZWJ
+    /// sequence, a base character plus combining marks) are drawn as a
+    /// single unit instead of being split apart.
+    Grapheme,
+}
+
 /// The `Haikunator` type
 /// Holds settings and data that will be used when `haikunate` is called.
 ///
@@ -19,21 +36,31 @@ mod default_nouns;
 ///     delimiter: "-",
 ///     token_length: 8,
 ///     token_hex: false,
-///     token_chars: "0123456789忠犬ハチ公"
+///     token_chars: "0123456789忠犬ハチ公",
+///     token_pattern: "",
+///     token_unit: haikunator::TokenUnit::Scalar,
 /// });
 ///
 /// ```
 ///
 /// **Note**: If `token_hex` is true, the value of `token_chars` is ignored.
+///
+/// **Note**: If `token_pattern` is non-empty, it takes precedence over
+/// `token_length`, `token_hex`, `token_chars` and `token_unit` (see
+/// [`Haikunator::draw_token`]).
 #[derive(Debug)]
 pub struct Haikunator<'a, R: Rng> {
     rng: RefCell<R>,
+    seen: RefCell<HashSet<String>>,
+    token_segments: OnceCell<CachedTokenSegments<'a>>,
     pub adjectives: &'a [&'a str],
     pub nouns: &'a [&'a str],
     pub delimiter: &'a str,
     pub token_length: usize,
     pub token_hex: bool,
     pub token_chars: &'a str,
+    pub token_pattern: &'a str,
+    pub token_unit: TokenUnit,
 }
 
 /// Parameters for `Haikunator::new_parametrized`.
@@ -45,6 +72,8 @@ pub struct HaikunatorParams<'a, R: Rng> {
     pub token_length: usize,
     pub token_hex: bool,
     pub token_chars: &'static str,
+    pub token_pattern: &'static str,
+    pub token_unit: TokenUnit,
 }
 
 impl Default for HaikunatorParams<'static, rand::rngs::ThreadRng> {
@@ -57,8 +86,156 @@ impl Default for HaikunatorParams<'static, rand::rngs::ThreadRng> {
             token_length: 4,
             token_hex: false,
             token_chars: "0123456789",
+            token_pattern: "",
+            token_unit: TokenUnit::Scalar,
+        }
+    }
+}
+
+/// A single piece of a parsed `token_pattern` (or the legacy-field
+/// equivalent): a pre-expanded alphabet, indexed by `TokenUnit`, together
+/// with how many units to draw from it.
+#[derive(Debug)]
+struct TokenSegment {
+    alphabet: Vec<String>,
+    min: usize,
+    max: usize,
+}
+
+/// The cached result of resolving the token configuration, together with
+/// the field values it was built from, so a later call can detect whether
+/// `token_length`/`token_hex`/`token_chars`/`token_pattern`/`token_unit`
+/// were mutated out from under it.
+#[derive(Debug)]
+struct CachedTokenSegments<'a> {
+    segments: Vec<TokenSegment>,
+    token_length: usize,
+    token_hex: bool,
+    token_chars: &'a str,
+    token_pattern: &'a str,
+    token_unit: TokenUnit,
+}
+
+/// Parses a `token_pattern` string into a sequence of `TokenSegment`s.
+///
+/// The grammar is a small subset of regex: character classes (`[a-f0-9]`,
+/// with `a-z`-style ranges), bare literal characters, and a `{n}` or
+/// `{m,n}` quantifier following a class or literal. `\`, `[`, `]`, `{` and
+/// `}` can be escaped with a leading backslash to use them literally.
+fn parse_token_pattern(pattern: &str) -> Vec<TokenSegment> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (alphabet, after_atom) = if chars[i] == '\\' && i + 1 < chars.len() {
+            (vec![chars[i + 1]], i + 2)
+        } else if chars[i] == '[' {
+            let close = find_unescaped(&chars, i + 1, ']')
+                .expect("unterminated character class in token pattern");
+            (expand_class(&chars[i + 1..close]), close + 1)
+        } else {
+            (vec![chars[i]], i + 1)
+        };
+
+        let (min, max, after_quantifier) = if after_atom < chars.len() && chars[after_atom] == '{'
+        {
+            let close = chars[after_atom..]
+                .iter()
+                .position(|&c| c == '}')
+                .map(|p| after_atom + p)
+                .expect("unterminated quantifier in token pattern");
+            let spec: String = chars[after_atom + 1..close].iter().collect();
+            let (min, max) = parse_quantifier(&spec);
+            (min, max, close + 1)
+        } else {
+            (1, 1, after_atom)
+        };
+
+        segments.push(TokenSegment {
+            alphabet: alphabet.into_iter().map(String::from).collect(),
+            min,
+            max,
+        });
+        i = after_quantifier;
+    }
+
+    segments
+}
+
+/// Scans `chars` from `start` for the first unescaped occurrence of
+/// `target`, skipping over `\`-escaped characters so an escaped `]` (or
+/// any other escaped character) inside a class doesn't get mistaken for
+/// its close.
+fn find_unescaped(chars: &[char], start: usize, target: char) -> Option<usize> {
+    let mut i = start;
+
+    while i < chars.len() {
+        if chars[i] == '\\' && i + 1 < chars.len() {
+            i += 2;
+        } else if chars[i] == target {
+            return Some(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    None
+}
+
+/// Expands the contents of a `[...]` character class into its alphabet,
+/// resolving `a-z`-style ranges and `\`-escapes.
+fn expand_class(chars: &[char]) -> Vec<char> {
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == '\\' && i + 1 < chars.len() {
+            out.push(chars[i + 1]);
+            i += 2;
+        } else if i + 2 < chars.len() && chars[i + 1] == '-' {
+            let start = chars[i];
+            let end = chars[i + 2];
+            assert!(
+                start <= end,
+                "invalid range in token pattern: {start}-{end}"
+            );
+            out.extend((start as u32..=end as u32).filter_map(char::from_u32));
+            i += 3;
+        } else {
+            out.push(chars[i]);
+            i += 1;
         }
     }
+
+    out
+}
+
+/// Parses a quantifier body (the part between `{` and `}`) into a
+/// `(min, max)` repeat count.
+fn parse_quantifier(spec: &str) -> (usize, usize) {
+    let (min, max) = match spec.split_once(',') {
+        Some((min_s, max_s)) => {
+            let min = min_s.parse().unwrap_or(0);
+            let max = if max_s.is_empty() {
+                min
+            } else {
+                max_s.parse().unwrap_or(min)
+            };
+            (min, max)
+        }
+        None => {
+            let n = spec.parse().unwrap_or(1);
+            (n, n)
+        }
+    };
+
+    assert!(
+        min <= max,
+        "invalid quantifier in token pattern: {{{min},{max}}} has min > max"
+    );
+
+    (min, max)
 }
 
 impl<'a, R: Rng> Haikunator<'a, R> {
@@ -66,15 +243,79 @@ impl<'a, R: Rng> Haikunator<'a, R> {
     pub fn new(params: HaikunatorParams<'a, R>) -> Self {
         Self {
             rng: RefCell::new(params.rng),
+            seen: RefCell::new(HashSet::new()),
+            token_segments: OnceCell::new(),
             adjectives: params.adjectives,
             nouns: params.nouns,
             delimiter: params.delimiter,
             token_length: params.token_length,
             token_hex: params.token_hex,
             token_chars: params.token_chars,
+            token_pattern: params.token_pattern,
+            token_unit: params.token_unit,
         }
     }
 
+    /// Resolves the current token configuration into `TokenSegment`s: either
+    /// the parsed `token_pattern`, or an equivalent single segment built from
+    /// `token_length`/`token_hex`/`token_chars`/`token_unit` when no pattern
+    /// is set. The result is computed once, on the first call, and cached
+    /// for the lifetime of this `Haikunator`, so `token_pattern` is parsed
+    /// (and the alphabet expanded) exactly once rather than on every draw.
+    ///
+    /// **Panics** if `token_length`, `token_hex`, `token_chars`,
+    /// `token_pattern` or `token_unit` have changed since the cache was
+    /// built — these fields are only read once, so mutating one of them
+    /// after the first call to `haikunate`/`draw_token`/`namespace_size`/
+    /// `haikunate_unique` would otherwise be silently ignored. Construct a
+    /// new `Haikunator` instead of mutating them after first use.
+    fn token_segments(&self) -> &[TokenSegment] {
+        let cached = self.token_segments.get_or_init(|| {
+            let segments = if !self.token_pattern.is_empty() {
+                parse_token_pattern(self.token_pattern)
+            } else {
+                let chars = if self.token_hex {
+                    "0123456789abcdef"
+                } else {
+                    self.token_chars
+                };
+
+                let alphabet = match self.token_unit {
+                    TokenUnit::Grapheme => chars.graphemes(true).map(String::from).collect(),
+                    TokenUnit::Scalar => chars.chars().map(String::from).collect(),
+                };
+
+                vec![TokenSegment {
+                    alphabet,
+                    min: self.token_length,
+                    max: self.token_length,
+                }]
+            };
+
+            CachedTokenSegments {
+                segments,
+                token_length: self.token_length,
+                token_hex: self.token_hex,
+                token_chars: self.token_chars,
+                token_pattern: self.token_pattern,
+                token_unit: self.token_unit,
+            }
+        });
+
+        assert!(
+            cached.token_length == self.token_length
+                && cached.token_hex == self.token_hex
+                && cached.token_chars == self.token_chars
+                && cached.token_pattern == self.token_pattern
+                && cached.token_unit == self.token_unit,
+            "token_length/token_hex/token_chars/token_pattern/token_unit mutated after the token \
+             configuration was already cached; construct a new Haikunator instead of mutating \
+             these fields after the first call to haikunate/draw_token/namespace_size/haikunate_unique"
+        );
+
+        &cached.segments
+    }
+
     /// Generates random heroku-like short names using a combination
     // of adjective, noun, and the delimiter.
     ///
@@ -87,38 +328,122 @@ impl<'a, R: Rng> Haikunator<'a, R> {
     /// println!("{:?}", h.haikunate());
     /// ```
     pub fn haikunate(&self) -> String {
-        let tokens = if self.token_hex {
-            "0123456789abcdef"
-        } else {
-            self.token_chars
-        };
+        let adjective = self.draw_adjective();
+        let noun = self.draw_noun();
+        let token = self.draw_token();
+
+        let mut parts = vec![adjective, noun, &token];
+        parts.retain(|s: &&str| !s.is_empty());
+        parts.join(self.delimiter)
+    }
+
+    /// Draws one adjective, or `""` if `adjectives` is empty. Shares the
+    /// same RNG as `draw_noun`/`draw_token`, so callers composing their own
+    /// layout out of these draws get a reproducible sequence under a seeded
+    /// RNG, just like `haikunate` does.
+    pub fn draw_adjective(&self) -> &'a str {
+        if self.adjectives.is_empty() {
+            return "";
+        }
 
         let mut rng = self.rng.borrow_mut();
-        let adjective = if !self.adjectives.is_empty() {
-            self.adjectives[rng.gen_range(0..self.adjectives.len())]
-        } else {
-            ""
-        };
+        self.adjectives[rng.gen_range(0..self.adjectives.len())]
+    }
 
-        let noun = if !self.nouns.is_empty() {
-            self.nouns[rng.gen_range(0..self.nouns.len())]
-        } else {
-            ""
-        };
+    /// Draws one noun, or `""` if `nouns` is empty.
+    pub fn draw_noun(&self) -> &'a str {
+        if self.nouns.is_empty() {
+            return "";
+        }
 
-        let mut token = String::with_capacity(self.token_length);
-        let count = tokens.chars().count();
+        let mut rng = self.rng.borrow_mut();
+        self.nouns[rng.gen_range(0..self.nouns.len())]
+    }
+
+    /// Draws one token, following `token_pattern` if set, or else
+    /// `token_length`/`token_hex`/`token_chars`/`token_unit`.
+    pub fn draw_token(&self) -> String {
+        let segments = self.token_segments();
+        let mut rng = self.rng.borrow_mut();
 
-        if count > 0 {
-            for _ in 0..self.token_length {
-                let index = rng.gen_range(0..count);
-                token.push(tokens.chars().nth(index).unwrap());
+        let mut token = String::new();
+        for segment in segments {
+            if segment.alphabet.is_empty() {
+                continue;
+            }
+
+            let len = if segment.min == segment.max {
+                segment.min
+            } else {
+                rng.gen_range(segment.min..=segment.max)
+            };
+
+            for _ in 0..len {
+                let index = rng.gen_range(0..segment.alphabet.len());
+                token.push_str(&segment.alphabet[index]);
             }
         }
 
-        let mut parts = vec![adjective, noun, &token];
-        parts.retain(|s: &&str| !s.is_empty());
-        parts.join(self.delimiter)
+        token
+    }
+
+    /// The number of distinct names this configuration can produce, i.e.
+    /// `max(1, adjectives.len()) * max(1, nouns.len()) * (token alphabet
+    /// combinations)`. Useful for sizing a keyspace before relying on
+    /// haikunated names as unique identifiers. Saturates at `u128::MAX`
+    /// rather than overflowing.
+    pub fn namespace_size(&self) -> u128 {
+        let adjectives = self.adjectives.len().max(1) as u128;
+        let nouns = self.nouns.len().max(1) as u128;
+
+        adjectives
+            .saturating_mul(nouns)
+            .saturating_mul(self.token_space_size())
+    }
+
+    /// The number of distinct tokens the current `token_segments` can
+    /// produce, summed across each segment's allowed repeat counts and
+    /// multiplied across segments.
+    fn token_space_size(&self) -> u128 {
+        self.token_segments()
+            .iter()
+            .map(|segment| {
+                let base = segment.alphabet.len() as u128;
+                if base == 0 {
+                    return 1;
+                }
+
+                (segment.min..=segment.max)
+                    .map(|len| base.saturating_pow(len as u32))
+                    .fold(0u128, |total, n| total.saturating_add(n))
+            })
+            .fold(1u128, |total, n| total.saturating_mul(n))
+    }
+
+    /// Generates a name like `haikunate`, but retries on collisions with
+    /// previously returned names (tracked internally), returning `None`
+    /// once the namespace is effectively exhausted.
+    pub fn haikunate_unique(&self) -> Option<String> {
+        const MAX_ATTEMPTS: usize = 1000;
+
+        if self.seen.borrow().len() as u128 >= self.namespace_size() {
+            return None;
+        }
+
+        for _ in 0..MAX_ATTEMPTS {
+            let name = self.haikunate();
+            if self.seen.borrow_mut().insert(name.clone()) {
+                return Some(name);
+            }
+        }
+
+        None
+    }
+
+    /// Returns an iterator that yields distinct names (as `haikunate_unique`
+    /// does) until the namespace is exhausted.
+    pub fn iter_unique(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::from_fn(move || self.haikunate_unique())
     }
 }
 
@@ -1,7 +1,8 @@
-use haikunator::{Haikunator, HaikunatorParams};
+use haikunator::{Haikunator, HaikunatorParams, TokenUnit};
 use regex::Regex;
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[test]
 fn it_returns_4_digits_token() {
@@ -142,6 +143,188 @@ fn it_handles_zero_length_parts_without_gen_range_panic() {
     h.haikunate(); // no panic
 }
 
+#[test]
+fn it_supports_token_pattern_hex_equivalent() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_pattern: "[a-f0-9]{4}",
+        ..Default::default()
+    });
+
+    let re = Regex::new(r"^\w+-\w+-[a-f0-9]{4}$").unwrap();
+
+    for _ in 0..100 {
+        assert!(re.is_match(&h.haikunate()));
+    }
+}
+
+#[test]
+fn it_supports_structured_token_pattern() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_pattern: "[A-Z]{2}[0-9]{3}",
+        ..Default::default()
+    });
+
+    let re = Regex::new(r"^\w+-\w+-[A-Z]{2}[0-9]{3}$").unwrap();
+
+    for _ in 0..100 {
+        assert!(re.is_match(&h.haikunate()));
+    }
+}
+
+#[test]
+fn it_supports_bounded_quantifier_token_pattern() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_pattern: "[0-9]{2,5}",
+        ..Default::default()
+    });
+
+    let re = Regex::new(r"^\w+-\w+-[0-9]{2,5}$").unwrap();
+
+    for _ in 0..100 {
+        assert!(re.is_match(&h.haikunate()));
+    }
+}
+
+#[test]
+#[should_panic(expected = "min > max")]
+fn it_rejects_reversed_quantifier_bounds_in_token_pattern() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_pattern: "[0-9]{5,2}",
+        ..Default::default()
+    });
+
+    h.haikunate();
+}
+
+#[test]
+fn it_supports_escaped_bracket_in_token_pattern_class() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_pattern: r"[a\]b]{3}",
+        ..Default::default()
+    });
+
+    let re = Regex::new(r"^\w+-\w+-[a\]b]{3}$").unwrap();
+
+    for _ in 0..100 {
+        assert!(re.is_match(&h.haikunate()));
+    }
+}
+
+#[test]
+fn it_supports_unicode_token_pattern() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_pattern: "[忠犬ハチ公]{5}",
+        ..Default::default()
+    });
+
+    let re = Regex::new(r"^\w+-\w+-[忠犬ハチ公]{5}$").unwrap();
+
+    for _ in 0..100 {
+        assert!(re.is_match(&h.haikunate()));
+    }
+}
+
+#[test]
+fn it_supports_grapheme_token_unit() {
+    let h = Haikunator::new(HaikunatorParams {
+        token_length: 3,
+        token_chars: "🇺🇸🇯🇵",
+        token_unit: TokenUnit::Grapheme,
+        ..Default::default()
+    });
+
+    for _ in 0..100 {
+        let name = h.haikunate();
+        let token = name.rsplit('-').next().unwrap();
+        let graphemes: Vec<&str> = token.graphemes(true).collect();
+
+        assert_eq!(graphemes.len(), 3);
+        assert!(graphemes.iter().all(|g| *g == "🇺🇸" || *g == "🇯🇵"));
+    }
+}
+
+#[test]
+fn it_computes_namespace_size() {
+    let h = Haikunator::new(HaikunatorParams {
+        adjectives: &["flying", "bubbly"],
+        nouns: &["bat", "soda"],
+        token_length: 2,
+        token_chars: "01",
+        ..Default::default()
+    });
+
+    // 2 adjectives * 2 nouns * 2^2 token combinations
+    assert_eq!(h.namespace_size(), 16);
+}
+
+#[test]
+fn it_generates_unique_names_until_exhausted() {
+    let h = Haikunator::new(HaikunatorParams {
+        adjectives: &["flying", "bubbly"],
+        nouns: &["bat", "soda"],
+        token_length: 1,
+        token_chars: "01",
+        ..Default::default()
+    });
+
+    let mut names = std::collections::HashSet::new();
+    for _ in 0..h.namespace_size() {
+        let name = h.haikunate_unique().expect("namespace not yet exhausted");
+        assert!(names.insert(name));
+    }
+
+    assert!(h.haikunate_unique().is_none());
+}
+
+#[test]
+fn it_exhausts_iter_unique_for_zero_length_parts() {
+    let mut h = Haikunator::default();
+    h.token_length = 0;
+    h.adjectives = &[];
+    h.nouns = &[];
+
+    let names: Vec<String> = h.iter_unique().collect();
+    assert_eq!(names.len(), 1);
+}
+
+#[test]
+fn it_composes_custom_layouts_from_draw_steps() {
+    let h = Haikunator::new(HaikunatorParams {
+        nouns: &["bat", "soda"],
+        token_length: 4,
+        token_chars: "0123456789",
+        ..Default::default()
+    });
+
+    let name = format!("{}-{}-{}", h.draw_noun(), h.draw_noun(), h.draw_token());
+    let re = Regex::new(r"^(bat|soda)-(bat|soda)-[0-9]{4}$").unwrap();
+
+    assert!(re.is_match(&name));
+}
+
+#[test]
+fn it_draws_empty_strings_for_empty_adjectives_and_nouns() {
+    let h = Haikunator::new(HaikunatorParams {
+        adjectives: &[],
+        nouns: &[],
+        ..Default::default()
+    });
+
+    assert_eq!(h.draw_adjective(), "");
+    assert_eq!(h.draw_noun(), "");
+}
+
+#[test]
+#[should_panic(expected = "mutated after the token configuration was already cached")]
+fn it_rejects_token_chars_mutation_after_first_draw() {
+    let mut h = Haikunator::default();
+    h.token_chars = "0";
+    h.haikunate();
+
+    h.token_chars = "9";
+    h.haikunate();
+}
+
 #[test]
 fn it_works_with_small_rng() {
     let params = HaikunatorParams {
@@ -152,6 +335,8 @@ fn it_works_with_small_rng() {
         token_length: 4,
         token_hex: false,
         token_chars: "0123456789",
+        token_pattern: "",
+        token_unit: TokenUnit::Scalar,
     };
 
     let h = Haikunator::new(params);